@@ -44,8 +44,23 @@ fn app() -> clap::Command<'static> {
             Arg::new("auth")
                 .short('a')
                 .long("auth")
-                .help("Authenticate with user and pass")
-                .value_name("user:pass"),
+                .help("Add an account with access rules, e.g. `user:pass@/share:rw`")
+                .value_name("rule")
+                .multiple_occurrences(true),
+        )
+        .arg(
+            Arg::new("tls-cert")
+                .long("tls-cert")
+                .help("Path to the TLS certificate (PEM) to serve over HTTPS")
+                .value_name("path")
+                .allow_invalid_utf8(true),
+        )
+        .arg(
+            Arg::new("tls-key")
+                .long("tls-key")
+                .help("Path to the TLS private key (PEM) to serve over HTTPS")
+                .value_name("path")
+                .allow_invalid_utf8(true),
         )
 }
 
@@ -59,7 +74,65 @@ pub struct Args {
     pub port: u16,
     pub path: PathBuf,
     pub readonly: bool,
-    pub auth: Option<String>,
+    pub auth: Vec<AuthRule>,
+    pub tls_cert: Option<PathBuf>,
+    pub tls_key: Option<PathBuf>,
+}
+
+/// One `--auth` rule: an optional `user:pass` credential granted `r` or `rw` access
+/// to a subtree of the served path. A credential-less rule (`@/public:r`) grants
+/// anonymous access.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct AuthRule {
+    pub user: Option<String>,
+    pub pass: Option<String>,
+    pub path: String,
+    pub readwrite: bool,
+}
+
+impl AuthRule {
+    /// Parse a rule of the form `user:pass@/path:rw`, `@/path:r`, or bare
+    /// `user:pass` (full read-write access to `/`, for backward compatibility).
+    fn parse(raw: &str) -> BoxResult<AuthRule> {
+        // Split on the *last* `@`: the path never contains one, but the password might.
+        let (cred, path_perm) = match raw.rsplit_once('@') {
+            Some((cred, rest)) => (cred, rest),
+            None => (raw, "/:rw"),
+        };
+        let (path, perm) = match path_perm.rsplit_once(':') {
+            Some((path, perm)) => (path, perm),
+            None => (path_perm, "rw"),
+        };
+        let readwrite = match perm {
+            "r" => false,
+            "rw" => true,
+            _ => bail!(
+                "error: invalid auth rule \"{}\": permission must be \"r\" or \"rw\"",
+                raw
+            ),
+        };
+        let path = if path.is_empty() { "/" } else { path }.to_owned();
+        // `split_once` keeps everything after the first `:` intact as `pass`, so a
+        // password containing `:` or (now that the separator above is the *last* `@`)
+        // `@` round-trips correctly; only the path is assumed free of both characters.
+        let (user, pass) = if cred.is_empty() {
+            (None, None)
+        } else {
+            match cred.split_once(':') {
+                Some((user, pass)) => (Some(user.to_owned()), Some(pass.to_owned())),
+                None => bail!(
+                    "error: invalid auth rule \"{}\": expected \"user:pass\" before \"@\"",
+                    raw
+                ),
+            }
+        };
+        Ok(AuthRule {
+            user,
+            pass,
+            path,
+            readwrite,
+        })
+    }
 }
 
 impl Args {
@@ -73,7 +146,15 @@ impl Args {
         let path = matches.value_of_os("path").unwrap_or_default();
         let path = Args::parse_path(path)?;
         let readonly = matches.is_present("no-edit");
-        let auth = matches.value_of("auth").map(|v| v.to_owned());
+        let auth = match matches.values_of("auth") {
+            Some(values) => values.map(AuthRule::parse).collect::<BoxResult<Vec<_>>>()?,
+            None => vec![],
+        };
+        let tls_cert = matches.value_of_os("tls-cert").map(PathBuf::from);
+        let tls_key = matches.value_of_os("tls-key").map(PathBuf::from);
+        if tls_cert.is_some() != tls_key.is_some() {
+            bail!("error: --tls-cert and --tls-key must be specified together");
+        }
 
         Ok(Args {
             address,
@@ -81,6 +162,8 @@ impl Args {
             path,
             readonly,
             auth,
+            tls_cert,
+            tls_key,
         })
     }
 
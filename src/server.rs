@@ -3,19 +3,22 @@ use crate::{Args, BoxResult};
 use async_walkdir::WalkDir;
 use async_zip::write::{EntryOptions, ZipFileWriter};
 use async_zip::Compression;
+use digest::Digest;
 use futures::stream::StreamExt;
 use futures::TryStreamExt;
 use hyper::header::HeaderValue;
 use hyper::service::{make_service_fn, service_fn};
 use hyper::{Body, Method, StatusCode};
-use percent_encoding::percent_decode;
+use percent_encoding::{percent_decode, utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
 use serde::Serialize;
 use std::convert::Infallible;
+use std::net::SocketAddr;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::SystemTime;
 use tokio::fs::File;
-use tokio::io::AsyncWrite;
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWrite, AsyncWriteExt};
 use tokio::{fs, io};
 use tokio_util::codec::{BytesCodec, FramedRead};
 use tokio_util::io::{ReaderStream, StreamReader};
@@ -36,9 +39,29 @@ const INDEX_HTML: &str = include_str!("index.html");
 const INDEX_CSS: &str = include_str!("index.css");
 const BUF_SIZE: usize = 1024 * 16;
 
+/// Characters a PROPFIND child's path segment must be percent-encoded against before
+/// it's embedded in a `<D:href>`, mirroring the decoding `get_file_path` does on the way in.
+const PATH_SEGMENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
 pub async fn serve(args: Args) -> BoxResult<()> {
     let address = args.address()?;
+    let tls_config = match (&args.tls_cert, &args.tls_key) {
+        (Some(cert), Some(key)) => Some(load_tls_config(cert, key)?),
+        _ => None,
+    };
     let inner = Arc::new(InnerService::new(args));
+
+    match tls_config {
+        Some(tls_config) => serve_tls(address, inner, tls_config).await,
+        None => serve_plain(address, inner).await,
+    }
+}
+
+async fn serve_plain(address: SocketAddr, inner: Arc<InnerService>) -> BoxResult<()> {
     let make_svc = make_service_fn(move |_| {
         let inner = inner.clone();
         async {
@@ -57,6 +80,88 @@ pub async fn serve(args: Args) -> BoxResult<()> {
     Ok(())
 }
 
+async fn serve_tls(
+    address: SocketAddr,
+    inner: Arc<InnerService>,
+    tls_config: rustls::ServerConfig,
+) -> BoxResult<()> {
+    let listener = tokio::net::TcpListener::bind(address).await?;
+    let acceptor = tokio_rustls::TlsAcceptor::from(Arc::new(tls_config));
+    eprintln!("Files served on https://{}", listener.local_addr()?);
+
+    loop {
+        let (stream, _) = match listener.accept().await {
+            Ok(accepted) => accepted,
+            Err(err) => {
+                error!("Failed to accept connection: {}", err);
+                continue;
+            }
+        };
+        let acceptor = acceptor.clone();
+        let inner = inner.clone();
+        tokio::spawn(async move {
+            let stream = match acceptor.accept(stream).await {
+                Ok(stream) => stream,
+                Err(err) => {
+                    error!("TLS handshake failed: {}", err);
+                    return;
+                }
+            };
+            let service = service_fn(move |req| {
+                let inner = inner.clone();
+                inner.call(req)
+            });
+            if let Err(err) = hyper::server::conn::Http::new()
+                .serve_connection(stream, service)
+                .await
+            {
+                error!("Failed to serve connection: {}", err);
+            }
+        });
+    }
+}
+
+/// Load a `rustls` server config from a PEM certificate chain and private key.
+fn load_tls_config(cert_path: &Path, key_path: &Path) -> BoxResult<rustls::ServerConfig> {
+    let certs = {
+        let file = std::fs::File::open(cert_path)?;
+        let mut reader = std::io::BufReader::new(file);
+        let certs = rustls_pemfile::certs(&mut reader)?;
+        if certs.is_empty() {
+            bail!(
+                "error: no certificate found in \"{}\"",
+                cert_path.display()
+            );
+        }
+        certs.into_iter().map(rustls::Certificate).collect()
+    };
+    let key = {
+        let key_bytes = std::fs::read(key_path)?;
+
+        let mut pkcs8 = rustls_pemfile::pkcs8_private_keys(&mut key_bytes.as_slice())?;
+        let mut rsa = rustls_pemfile::rsa_private_keys(&mut key_bytes.as_slice())?;
+        let mut ec = rustls_pemfile::ec_private_keys(&mut key_bytes.as_slice())?;
+
+        if let Some(key) = pkcs8.pop() {
+            rustls::PrivateKey(key)
+        } else if let Some(key) = rsa.pop() {
+            rustls::PrivateKey(key)
+        } else if let Some(key) = ec.pop() {
+            rustls::PrivateKey(key)
+        } else {
+            bail!(
+                "error: no PKCS8, PKCS1 or EC private key found in \"{}\"",
+                key_path.display()
+            );
+        }
+    };
+    let config = rustls::ServerConfig::builder()
+        .with_safe_defaults()
+        .with_no_client_auth()
+        .with_single_cert(certs, key)?;
+    Ok(config)
+}
+
 struct InnerService {
     args: Args,
 }
@@ -78,22 +183,40 @@ impl InnerService {
     }
 
     pub async fn handle(self: Arc<Self>, req: Request) -> BoxResult<Response> {
-        if !self.auth_guard(&req).unwrap_or_default() {
+        let access = self.auth_guard(&req).unwrap_or(Access::Deny);
+        if access == Access::Deny {
             let mut res = status_code!(StatusCode::UNAUTHORIZED);
             res.headers_mut()
                 .insert("WWW-Authenticate", HeaderValue::from_static("Basic"));
             return Ok(res);
         }
 
+        let is_write = matches!(
+            req.method().as_str(),
+            "PUT" | "DELETE" | "MKCOL" | "MOVE" | "COPY"
+        );
+        if is_write && (self.args.readonly || access != Access::ReadWrite) {
+            return Ok(status_code!(StatusCode::FORBIDDEN));
+        }
+
         if req.method() == Method::GET {
             self.handle_static(req).await
+        } else if req.method() == Method::HEAD {
+            self.handle_head(req).await
         } else if req.method() == Method::PUT {
-            if self.args.readonly {
-                return Ok(status_code!(StatusCode::FORBIDDEN));
-            }
             self.handle_upload(req).await
         } else if req.method() == Method::DELETE {
             self.handle_delete(req).await
+        } else if req.method() == Method::OPTIONS {
+            self.handle_options()
+        } else if req.method().as_str() == "PROPFIND" {
+            self.handle_propfind(req).await
+        } else if req.method().as_str() == "MKCOL" {
+            self.handle_mkcol(req).await
+        } else if req.method().as_str() == "MOVE" {
+            self.handle_copy_or_move(req, true).await
+        } else if req.method().as_str() == "COPY" {
+            self.handle_copy_or_move(req, false).await
         } else {
             return Ok(status_code!(StatusCode::NOT_FOUND));
         }
@@ -112,12 +235,25 @@ impl InnerService {
                     if req_query == "zip" {
                         return self.handle_send_dir_zip(path.as_path()).await;
                     }
+                    if req_query == "tar" {
+                        return self.handle_send_dir_tar(path.as_path(), false).await;
+                    }
+                    if req_query == "targz" {
+                        return self.handle_send_dir_tar(path.as_path(), true).await;
+                    }
                     if let Some(q) = req_query.strip_prefix("q=") {
                         return self.handle_query_dir(path.as_path(), q).await;
                     }
                     self.handle_ls_dir(path.as_path(), true).await
                 } else {
-                    self.handle_send_file(path.as_path()).await
+                    if let Some(res) = self.try_not_modified(&meta, req.headers())? {
+                        return Ok(res);
+                    }
+                    let range = req
+                        .headers()
+                        .get(hyper::header::RANGE)
+                        .and_then(|v| v.to_str().ok());
+                    self.handle_send_file(path.as_path(), &meta, range).await
                 }
             }
             Err(_) => {
@@ -130,6 +266,40 @@ impl InnerService {
         }
     }
 
+    /// Handle `HEAD`: for a file, the headers a `GET` without `Range` would send, with no
+    /// body. A directory gets a bare `200` with `Content-Length: 0`, and a missing path
+    /// gets `404`.
+    async fn handle_head(&self, req: Request) -> BoxResult<Response> {
+        let path = match self.get_file_path(req.uri().path())? {
+            Some(path) => path,
+            None => return Ok(status_code!(StatusCode::FORBIDDEN)),
+        };
+        match fs::metadata(&path).await {
+            Ok(meta) => {
+                if meta.is_dir() {
+                    Ok(hyper::Response::builder()
+                        .header("Content-Length", "0")
+                        .body(Body::empty())
+                        .unwrap())
+                } else {
+                    if let Some(res) = self.try_not_modified(&meta, req.headers())? {
+                        return Ok(res);
+                    }
+                    let mime = mime_guess::from_path(&path).first_or_octet_stream();
+                    Ok(hyper::Response::builder()
+                        .header("Accept-Ranges", "bytes")
+                        .header("Content-Type", mime.as_ref())
+                        .header("Last-Modified", last_modified(&meta))
+                        .header("ETag", etag(&meta))
+                        .header("Content-Length", meta.len().to_string())
+                        .body(Body::empty())
+                        .unwrap())
+                }
+            }
+            Err(_) => Ok(status_code!(StatusCode::NOT_FOUND)),
+        }
+    }
+
     async fn handle_upload(&self, mut req: Request) -> BoxResult<Response> {
         let forbidden = status_code!(StatusCode::FORBIDDEN);
         let path = match self.get_file_path(req.uri().path())? {
@@ -149,19 +319,76 @@ impl InnerService {
             None => return Ok(forbidden),
         }
 
-        let mut file = fs::File::create(path).await?;
+        let require_absent =
+            req.headers().get("If-None-Match").and_then(|v| v.to_str().ok()) == Some("*");
+        if require_absent && fs::metadata(&path).await.is_ok() {
+            return Ok(status_code!(StatusCode::PRECONDITION_FAILED));
+        }
+
+        let mut expected_digest = match parse_expected_digest(req.headers()) {
+            Ok(digest) => digest,
+            Err(_) => return Ok(status_code!(StatusCode::BAD_REQUEST)),
+        };
+
+        let part_path = upload_part_path(&path);
+        let mut file = fs::File::create(&part_path).await?;
 
         let body_with_io_error = req
             .body_mut()
             .map_err(|err| io::Error::new(io::ErrorKind::Other, err));
-
         let body_reader = StreamReader::new(body_with_io_error);
-
         futures::pin_mut!(body_reader);
 
-        io::copy(&mut body_reader, &mut file).await?;
+        // Any failure copying the body (dropped connection, stream error, disk
+        // error) must still clean up `part_path` before propagating, the same
+        // as the digest-mismatch case below.
+        let copy_result: io::Result<()> = async {
+            let mut buf = vec![0u8; BUF_SIZE];
+            loop {
+                let n = body_reader.read(&mut buf).await?;
+                if n == 0 {
+                    break;
+                }
+                file.write_all(&buf[..n]).await?;
+                if let Some((hasher, _)) = expected_digest.as_mut() {
+                    hasher.update(&buf[..n]);
+                }
+            }
+            file.flush().await
+        }
+        .await;
+        drop(file);
+        if let Err(err) = copy_result {
+            let _ = fs::remove_file(&part_path).await;
+            return Err(err.into());
+        }
+
+        if let Some((mut hasher, expected)) = expected_digest {
+            if hasher.finalize_reset().as_ref() != expected.as_slice() {
+                let _ = fs::remove_file(&part_path).await;
+                return Ok(status_code!(StatusCode::BAD_REQUEST));
+            }
+        }
+
+        if require_absent {
+            // `hard_link` atomically fails with `AlreadyExists` if `path` now exists,
+            // unlike `rename`, which would silently clobber it. This closes the race
+            // between the existence check above and the rename below.
+            match fs::hard_link(&part_path, &path).await {
+                Ok(()) => {
+                    let _ = fs::remove_file(&part_path).await;
+                }
+                Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {
+                    let _ = fs::remove_file(&part_path).await;
+                    return Ok(status_code!(StatusCode::PRECONDITION_FAILED));
+                }
+                Err(err) => return Err(err.into()),
+            }
+        } else {
+            fs::rename(&part_path, &path).await?;
+        }
 
-        return Ok(status_code!(StatusCode::OK));
+        Ok(status_code!(StatusCode::OK))
     }
 
     async fn handle_delete(&self, req: Request) -> BoxResult<Response> {
@@ -179,6 +406,131 @@ impl InnerService {
         Ok(status_code!(StatusCode::OK))
     }
 
+    fn handle_options(&self) -> BoxResult<Response> {
+        let allow = if self.args.readonly {
+            "GET, HEAD, OPTIONS, PROPFIND"
+        } else {
+            "GET, HEAD, OPTIONS, PUT, DELETE, PROPFIND, MKCOL, MOVE, COPY"
+        };
+        Ok(hyper::Response::builder()
+            .header("DAV", "1")
+            .header("Allow", allow)
+            .header("Content-Length", "0")
+            .body(Body::empty())
+            .unwrap())
+    }
+
+    async fn handle_propfind(&self, req: Request) -> BoxResult<Response> {
+        let req_path = req.uri().path().to_owned();
+        let path = match self.get_file_path(&req_path)? {
+            Some(path) => path,
+            None => return Ok(status_code!(StatusCode::FORBIDDEN)),
+        };
+        let meta = match fs::metadata(&path).await {
+            Ok(meta) => meta,
+            Err(_) => return Ok(status_code!(StatusCode::NOT_FOUND)),
+        };
+        let depth = req
+            .headers()
+            .get("Depth")
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("1");
+
+        let mut body =
+            String::from(r#"<?xml version="1.0" encoding="utf-8"?><D:multistatus xmlns:D="DAV:">"#);
+        body.push_str(&propfind_entry(&req_path, &meta));
+
+        if meta.is_dir() && depth != "0" {
+            let mut rd = fs::read_dir(&path).await?;
+            while let Some(entry) = rd.next_entry().await? {
+                let child_meta = match entry.metadata().await {
+                    Ok(meta) => meta,
+                    Err(_) => continue,
+                };
+                let name = entry.file_name().to_string_lossy().into_owned();
+                let encoded_name = utf8_percent_encode(&name, PATH_SEGMENT).to_string();
+                let child_href = format!("{}/{}", req_path.trim_end_matches('/'), encoded_name);
+                body.push_str(&propfind_entry(&child_href, &child_meta));
+            }
+        }
+        body.push_str("</D:multistatus>");
+
+        Ok(hyper::Response::builder()
+            .status(StatusCode::MULTI_STATUS)
+            .header("Content-Type", "application/xml; charset=utf-8")
+            .body(body.into())
+            .unwrap())
+    }
+
+    async fn handle_mkcol(&self, req: Request) -> BoxResult<Response> {
+        let path = match self.get_file_path(req.uri().path())? {
+            Some(path) => path,
+            None => return Ok(status_code!(StatusCode::FORBIDDEN)),
+        };
+        if fs::metadata(&path).await.is_ok() {
+            return Ok(status_code!(StatusCode::FORBIDDEN));
+        }
+        match path.parent() {
+            Some(parent) if fs::metadata(parent).await.map(|m| m.is_dir()).unwrap_or(false) => {
+                fs::create_dir(&path).await?;
+                Ok(status_code!(StatusCode::CREATED))
+            }
+            _ => Ok(status_code!(StatusCode::CONFLICT)),
+        }
+    }
+
+    async fn handle_copy_or_move(&self, req: Request, is_move: bool) -> BoxResult<Response> {
+        let src_path = match self.get_file_path(req.uri().path())? {
+            Some(path) => path,
+            None => return Ok(status_code!(StatusCode::FORBIDDEN)),
+        };
+        let dest = match req
+            .headers()
+            .get("Destination")
+            .and_then(|v| v.to_str().ok())
+        {
+            Some(dest) => dest,
+            None => return Ok(status_code!(StatusCode::BAD_REQUEST)),
+        };
+        let dest_req_path = match dest.parse::<hyper::Uri>() {
+            Ok(uri) => uri.path().to_owned(),
+            Err(_) => return Ok(status_code!(StatusCode::BAD_REQUEST)),
+        };
+        if !dest_req_path.starts_with('/') {
+            return Ok(status_code!(StatusCode::BAD_REQUEST));
+        }
+        let dest_access = self
+            .auth_guard_path(&req, &dest_req_path)
+            .unwrap_or(Access::Deny);
+        if dest_access != Access::ReadWrite {
+            return Ok(status_code!(StatusCode::FORBIDDEN));
+        }
+        let dest_path = match self.get_file_path(&dest_req_path)? {
+            Some(path) => path,
+            None => return Ok(status_code!(StatusCode::FORBIDDEN)),
+        };
+
+        if fs::metadata(&src_path).await.is_err() {
+            return Ok(status_code!(StatusCode::NOT_FOUND));
+        }
+        match dest_path.parent() {
+            Some(parent) if fs::metadata(parent).await.map(|m| m.is_dir()).unwrap_or(false) => {}
+            _ => return Ok(status_code!(StatusCode::CONFLICT)),
+        }
+
+        if is_move {
+            fs::rename(&src_path, &dest_path).await?;
+        } else {
+            let meta = fs::metadata(&src_path).await?;
+            if meta.is_dir() {
+                copy_dir_all(&src_path, &dest_path).await?;
+            } else {
+                fs::copy(&src_path, &dest_path).await?;
+            }
+        }
+        Ok(status_code!(StatusCode::CREATED))
+    }
+
     async fn handle_ls_dir(&self, path: &Path, exist: bool) -> BoxResult<Response> {
         let mut paths: Vec<PathItem> = vec![];
         if exist {
@@ -230,13 +582,78 @@ impl InnerService {
         Ok(Response::new(body))
     }
 
-    async fn handle_send_file(&self, path: &Path) -> BoxResult<Response> {
-        let file = fs::File::open(path).await?;
-        let stream = FramedRead::new(file, BytesCodec::new());
+    async fn handle_send_dir_tar(&self, path: &Path, gzip: bool) -> BoxResult<Response> {
+        let (writer, reader) = tokio::io::duplex(BUF_SIZE);
+        let path = path.to_owned();
+        tokio::spawn(async move {
+            let result = if gzip {
+                dir_tar(async_compression::tokio::write::GzipEncoder::new(writer), &path).await
+            } else {
+                dir_tar(writer, &path).await
+            };
+            if let Err(e) = result {
+                error!("Fail to tar {}, {}", path.display(), e.to_string());
+            }
+        });
+        let stream = ReaderStream::new(reader);
         let body = Body::wrap_stream(stream);
         Ok(Response::new(body))
     }
 
+    async fn handle_send_file(
+        &self,
+        path: &Path,
+        meta: &fs::Metadata,
+        range: Option<&str>,
+    ) -> BoxResult<Response> {
+        let mut file = fs::File::open(path).await?;
+        let size = meta.len();
+        let mime = mime_guess::from_path(path).first_or_octet_stream();
+
+        let builder = hyper::Response::builder()
+            .header("Accept-Ranges", "bytes")
+            .header("Content-Type", mime.as_ref())
+            .header("Last-Modified", last_modified(meta))
+            .header("ETag", etag(meta));
+
+        let range = match range.map(|range| parse_range(range, size)) {
+            Some(ParsedRange::Satisfiable(start, end)) => Some((start, end)),
+            Some(ParsedRange::Unsatisfiable) => {
+                return Ok(builder
+                    .status(StatusCode::RANGE_NOT_SATISFIABLE)
+                    .header("Content-Range", format!("bytes */{}", size))
+                    .body(Body::empty())
+                    .unwrap())
+            }
+            // A syntactically malformed Range header is ignored per RFC 7233 and
+            // falls back to a normal full-body response, same as no header at all.
+            Some(ParsedRange::Unparseable) | None => None,
+        };
+
+        match range {
+            Some((start, end)) => {
+                file.seek(io::SeekFrom::Start(start)).await?;
+                let take_len = end - start + 1;
+                let stream = FramedRead::new(file.take(take_len), BytesCodec::new());
+                let body = Body::wrap_stream(stream);
+                Ok(builder
+                    .status(StatusCode::PARTIAL_CONTENT)
+                    .header("Content-Range", format!("bytes {}-{}/{}", start, end, size))
+                    .header("Content-Length", take_len.to_string())
+                    .body(body)
+                    .unwrap())
+            }
+            None => {
+                let stream = FramedRead::new(file, BytesCodec::new());
+                let body = Body::wrap_stream(stream);
+                Ok(builder
+                    .header("Content-Length", size.to_string())
+                    .body(body)
+                    .unwrap())
+            }
+        }
+    }
+
     fn send_index(&self, path: &Path, mut paths: Vec<PathItem>) -> BoxResult<Response> {
         paths.sort_unstable();
         let breadcrumb = self.get_breadcrumb(path);
@@ -253,23 +670,99 @@ impl InnerService {
         Ok(hyper::Response::builder().body(output.into()).unwrap())
     }
 
-    fn auth_guard(&self, req: &Request) -> BoxResult<bool> {
-        if let Some(auth) = &self.args.auth {
-            if let Some(value) = req.headers().get("Authorization") {
-                let value = value.to_str()?;
-                let value = if value.contains("Basic ") {
-                    &value[6..]
+    /// Check `If-None-Match` / `If-Modified-Since` against the file's current `ETag` and
+    /// mtime, returning a `304 Not Modified` response when the client's copy is fresh.
+    fn try_not_modified(
+        &self,
+        meta: &fs::Metadata,
+        headers: &hyper::HeaderMap,
+    ) -> BoxResult<Option<Response>> {
+        let etag = etag(meta);
+        let not_modified = if let Some(if_none_match) = headers.get("If-None-Match") {
+            if_none_match.to_str()? == etag
+        } else if let Some(if_modified_since) = headers.get("If-Modified-Since") {
+            match httpdate::parse_http_date(if_modified_since.to_str()?) {
+                // HTTP dates have whole-second precision, so compare at that precision.
+                Ok(since) => {
+                    let secs = |t: SystemTime| {
+                        t.duration_since(SystemTime::UNIX_EPOCH)
+                            .map(|d| d.as_secs())
+                            .unwrap_or_default()
+                    };
+                    secs(meta.modified()?) <= secs(since)
+                }
+                Err(_) => false,
+            }
+        } else {
+            false
+        };
+        if not_modified {
+            Ok(Some(
+                hyper::Response::builder()
+                    .status(StatusCode::NOT_MODIFIED)
+                    .header("ETag", etag)
+                    .body(Body::empty())
+                    .unwrap(),
+            ))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Resolve the highest access level any matching `--auth` rule grants the
+    /// requester for `req`'s path. With no `--auth` rules configured, everyone gets
+    /// `ReadWrite` (the global `--no-edit` flag is checked separately by `handle`).
+    fn auth_guard(&self, req: &Request) -> BoxResult<Access> {
+        let req_path = req.uri().path().to_owned();
+        self.auth_guard_path(req, &req_path)
+    }
+
+    /// Like `auth_guard`, but checks `req_path` instead of `req`'s own URI path. Used
+    /// by `handle_copy_or_move` to scope the `Destination` header against the same
+    /// `--auth` rules, not just the MOVE/COPY source path.
+    fn auth_guard_path(&self, req: &Request, req_path: &str) -> BoxResult<Access> {
+        if self.args.auth.is_empty() {
+            return Ok(Access::ReadWrite);
+        }
+
+        // `rule.path` is the plain path the admin typed; `req_path` is still
+        // percent-encoded as the client sent it, so decode before comparing.
+        let req_path = percent_decode(req_path.as_bytes()).decode_utf8()?.into_owned();
+
+        // An absent, non-Basic, or malformed Authorization header is treated as "no
+        // credentials" rather than an outright deny, so anonymous `@/path:r` rules
+        // still grant access to clients sending stale or unsupported auth schemes.
+        let creds = req
+            .headers()
+            .get("Authorization")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Basic "))
+            .and_then(|value| base64::decode(value).ok())
+            .and_then(|value| String::from_utf8(value).ok())
+            .and_then(|value| value.split_once(':').map(|(u, p)| (u.to_owned(), p.to_owned())));
+
+        let mut access = Access::Deny;
+        for rule in &self.args.auth {
+            if !path_in_scope(&req_path, &rule.path) {
+                continue;
+            }
+            let granted = match (&rule.user, &rule.pass) {
+                (None, None) => true,
+                (Some(user), Some(pass)) => {
+                    creds.as_ref() == Some(&(user.to_owned(), pass.to_owned()))
+                }
+                _ => false,
+            };
+            if granted {
+                let level = if rule.readwrite {
+                    Access::ReadWrite
                 } else {
-                    return Ok(false);
+                    Access::ReadOnly
                 };
-                let value = base64::decode(value)?;
-                let value = std::str::from_utf8(&value)?;
-                return Ok(value == auth);
-            } else {
-                return Ok(false);
+                access = access.max(level);
             }
         }
-        Ok(true)
+        Ok(access)
     }
 
     fn get_breadcrumb(&self, path: &Path) -> String {
@@ -282,10 +775,11 @@ impl InnerService {
 
     fn get_file_path(&self, path: &str) -> BoxResult<Option<PathBuf>> {
         let decoded_path = percent_decode(path[1..].as_bytes()).decode_utf8()?;
+        let collapsed = collapse_dot_segments(&decoded_path);
         let slashes_switched = if cfg!(windows) {
-            decoded_path.replace('/', "\\")
+            collapsed.replace('/', "\\")
         } else {
-            decoded_path.into_owned()
+            collapsed
         };
         let path = self.args.path.join(&slashes_switched);
         if path.starts_with(&self.args.path) {
@@ -296,6 +790,14 @@ impl InnerService {
     }
 }
 
+/// Access level granted to a requester for a given path, resolved from `--auth` rules.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, PartialOrd, Ord)]
+enum Access {
+    Deny,
+    ReadOnly,
+    ReadWrite,
+}
+
 #[derive(Debug, Serialize, Eq, PartialEq, Ord, PartialOrd)]
 struct IndexData {
     breadcrumb: String,
@@ -350,6 +852,189 @@ async fn get_path_item<P: AsRef<Path>>(path: P, base_path: P) -> BoxResult<PathI
     })
 }
 
+/// Result of parsing a `Range` header, distinguishing a header that doesn't parse
+/// as a single byte-range-spec at all (ignored, per RFC 7233 falls back to `200`)
+/// from one that parses but names a range outside the resource (`416`).
+enum ParsedRange {
+    Satisfiable(u64, u64),
+    Unsatisfiable,
+    Unparseable,
+}
+
+/// Parse a `Range: bytes=start-end` header value into an inclusive `(start, end)` byte
+/// range, validated against `size`. A range with `start > end` or `end >= size` is
+/// `Unsatisfiable`; a value that isn't a single byte-range-spec is `Unparseable`.
+fn parse_range(range: &str, size: u64) -> ParsedRange {
+    fn parse(range: &str, size: u64) -> Option<(u64, u64)> {
+        let range = range.strip_prefix("bytes=")?;
+        // Reject multi-range lists (e.g. `bytes=0-10,20-30`); only a single
+        // byte-range-spec is supported.
+        if range.contains(',') {
+            return None;
+        }
+        let (start, end) = range.split_once('-')?;
+        if start.is_empty() {
+            let suffix_len: u64 = end.parse().ok()?;
+            let suffix_len = suffix_len.min(size);
+            Some((size - suffix_len, size.saturating_sub(1)))
+        } else {
+            let start: u64 = start.parse().ok()?;
+            let end = if end.is_empty() {
+                size.saturating_sub(1)
+            } else {
+                end.parse().ok()?
+            };
+            Some((start, end))
+        }
+    }
+
+    match parse(range, size) {
+        Some((start, end)) if start <= end && end < size => ParsedRange::Satisfiable(start, end),
+        Some(_) => ParsedRange::Unsatisfiable,
+        None => ParsedRange::Unparseable,
+    }
+}
+
+/// Build a weak `ETag` from a file's length and mtime.
+fn etag(meta: &fs::Metadata) -> String {
+    let mtime = meta
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    format!(r#"W/"{:x}-{:x}""#, mtime, meta.len())
+}
+
+fn last_modified(meta: &fs::Metadata) -> String {
+    match meta.modified() {
+        Ok(mtime) => httpdate::fmt_http_date(mtime),
+        Err(_) => httpdate::fmt_http_date(SystemTime::now()),
+    }
+}
+
+/// Build a single WebDAV `<D:response>` element describing `href`.
+fn propfind_entry(href: &str, meta: &fs::Metadata) -> String {
+    let is_dir = meta.is_dir();
+    let resourcetype = if is_dir { "<D:collection/>" } else { "" };
+    let content_length = if is_dir {
+        String::new()
+    } else {
+        format!("<D:getcontentlength>{}</D:getcontentlength>", meta.len())
+    };
+    let last_modified = match meta.modified() {
+        Ok(mtime) => format!(
+            "<D:getlastmodified>{}</D:getlastmodified>",
+            httpdate::fmt_http_date(mtime)
+        ),
+        Err(_) => String::new(),
+    };
+    format!(
+        "<D:response><D:href>{}</D:href><D:propstat><D:prop>\
+         <D:resourcetype>{}</D:resourcetype>{}{}</D:prop>\
+         <D:status>HTTP/1.1 200 OK</D:status></D:propstat></D:response>",
+        xml_escape(href),
+        resourcetype,
+        content_length,
+        last_modified,
+    )
+}
+
+fn xml_escape(value: &str) -> String {
+    value.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// Recursively copy a directory tree, used by the WebDAV `COPY` method.
+async fn copy_dir_all(src: &Path, dst: &Path) -> BoxResult<()> {
+    fs::create_dir_all(dst).await?;
+    let mut stack = vec![(src.to_path_buf(), dst.to_path_buf())];
+    while let Some((src_dir, dst_dir)) = stack.pop() {
+        let mut rd = fs::read_dir(&src_dir).await?;
+        while let Some(entry) = rd.next_entry().await? {
+            let entry_src = entry.path();
+            let entry_dst = dst_dir.join(entry.file_name());
+            let meta = entry.metadata().await?;
+            if meta.is_dir() {
+                fs::create_dir_all(&entry_dst).await?;
+                stack.push((entry_src, entry_dst));
+            } else {
+                fs::copy(&entry_src, &entry_dst).await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Parse a `Content-MD5` or `Digest: sha-256=...` request header into the hasher to
+/// verify the uploaded body against, plus the client-supplied (base64-decoded) digest.
+fn parse_expected_digest(
+    headers: &hyper::HeaderMap,
+) -> BoxResult<Option<(Box<dyn digest::DynDigest>, Vec<u8>)>> {
+    if let Some(value) = headers.get("Content-MD5").and_then(|v| v.to_str().ok()) {
+        let expected = base64::decode(value)?;
+        return Ok(Some((Box::new(md5::Md5::new()), expected)));
+    }
+    if let Some(value) = headers.get("Digest").and_then(|v| v.to_str().ok()) {
+        if let Some(encoded) = value
+            .strip_prefix("sha-256=")
+            .or_else(|| value.strip_prefix("SHA-256="))
+        {
+            let expected = base64::decode(encoded)?;
+            return Ok(Some((Box::new(sha2::Sha256::new()), expected)));
+        }
+    }
+    Ok(None)
+}
+
+/// Temporary file an upload is written to before being atomically renamed into place.
+/// Give each upload its own `.part` file so two concurrent `PUT`s to the same
+/// destination don't interleave writes into a single file before whichever
+/// finishes last wins the rename.
+static UPLOAD_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+fn upload_part_path(path: &Path) -> PathBuf {
+    let unique = UPLOAD_COUNTER.fetch_add(1, Ordering::Relaxed);
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{}.{}.part", std::process::id(), unique));
+    PathBuf::from(name)
+}
+
+/// Lexically collapse `.`/`..` segments in a `/`-separated path, the way a browser
+/// resolves a relative URL, so callers can safely prefix-check or join the result
+/// without a later `..` walking back out of the directory it was joined onto.
+/// A leading `/` is preserved if present; excess `..` at the root are just dropped
+/// rather than escaping above it.
+fn collapse_dot_segments(path: &str) -> String {
+    let absolute = path.starts_with('/');
+    let mut segments: Vec<&str> = Vec::new();
+    for segment in path.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            segment => segments.push(segment),
+        }
+    }
+    if absolute {
+        format!("/{}", segments.join("/"))
+    } else {
+        segments.join("/")
+    }
+}
+
+/// Check whether `req_path` falls under the subtree rooted at `rule_path`. `req_path`
+/// is collapsed first so a `..` segment can't walk a request back out of the scoped
+/// subtree while still passing this prefix check.
+fn path_in_scope(req_path: &str, rule_path: &str) -> bool {
+    let req_path = collapse_dot_segments(req_path);
+    let rule_path = rule_path.trim_end_matches('/');
+    if rule_path.is_empty() {
+        return true;
+    }
+    req_path == rule_path || req_path.starts_with(&format!("{}/", rule_path))
+}
+
 fn normalize_path<P: AsRef<Path>>(path: P) -> String {
     let path = path.as_ref().to_str().unwrap_or_default();
     if cfg!(windows) {
@@ -384,4 +1069,41 @@ async fn dir_zip<W: AsyncWrite + Unpin>(writer: &mut W, dir: &Path) -> BoxResult
     }
     writer.close().await?;
     Ok(())
+}
+
+async fn dir_tar<W: AsyncWrite + Unpin>(writer: W, dir: &Path) -> BoxResult<()> {
+    let mut builder = tokio_tar::Builder::new(writer);
+    let mut walkdir = WalkDir::new(dir);
+    while let Some(entry) = walkdir.next().await {
+        if let Ok(entry) = entry {
+            let meta = match fs::symlink_metadata(entry.path()).await {
+                Ok(meta) => meta,
+                Err(_) => continue,
+            };
+            let filepath = entry.path();
+            let relpath = match filepath.strip_prefix(dir) {
+                Ok(v) => v,
+                Err(_) => continue,
+            };
+            if meta.file_type().is_symlink() {
+                let target = match fs::read_link(&filepath).await {
+                    Ok(target) => target,
+                    Err(_) => continue,
+                };
+                let mut header = tokio_tar::Header::new_gnu();
+                header.set_entry_type(tokio_tar::EntryType::Symlink);
+                header.set_size(0);
+                header.set_mode(0o777);
+                header.set_cksum();
+                builder.append_link(&mut header, relpath, &target).await?;
+            } else if meta.is_file() {
+                let mut file = File::open(&filepath).await?;
+                builder.append_file(relpath, &mut file).await?;
+            }
+        }
+    }
+    builder.finish().await?;
+    let mut writer = builder.into_inner().await?;
+    writer.shutdown().await?;
+    Ok(())
 }
\ No newline at end of file